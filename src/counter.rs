@@ -0,0 +1,79 @@
+use anyhow::bail;
+
+use crate::{
+    message::{ErrorCode, Message, Payload},
+    node::Node,
+    runner::Runner,
+};
+
+const KV_SERVICE: &str = "seq-kv";
+const COUNTER_KEY: &str = "counter";
+
+/// `Node` impl for the grow-only counter workload, backed by Maelstrom's
+/// `seq-kv` service.
+///
+/// Not yet wired up in `main`; swap it in for `EchoBroadcast` to run this
+/// workload instead.
+#[allow(dead_code)]
+#[derive(Default)]
+pub struct Counter;
+
+impl Node for Counter {
+    fn handle(&mut self, runner: &Runner<Self>, msg: Message) -> anyhow::Result<()> {
+        match &msg.body.payload {
+            Payload::Init { node_id, .. } => {
+                runner.set_node_id(node_id.clone());
+                runner.reply(&msg, Payload::InitOk)?;
+            },
+            Payload::InitOk => bail!("node received init_ok message"),
+
+            Payload::Add { delta } => {
+                let delta = *delta;
+                return add(runner, delta, msg);
+            },
+            Payload::AddOk => {},
+
+            Payload::CounterRead => {
+                runner.kv_read(KV_SERVICE, COUNTER_KEY.into(), move |runner, reply| {
+                    let value = current_value(&reply)?;
+                    return runner.reply(&msg, Payload::CounterReadOk { value });
+                })?;
+            },
+            Payload::CounterReadOk { .. } => {},
+
+            _ => {},
+        };
+
+        return Ok(());
+    }
+}
+
+/// Reads the current count, then CAS-retries it up to `current + delta`,
+/// creating the key on the first write. Retries on a failed precondition
+/// (another node raced us) until the CAS succeeds.
+fn add(runner: &Runner<Counter>, delta: i64, msg: Message) -> anyhow::Result<()> {
+    return runner.kv_read(KV_SERVICE, COUNTER_KEY.into(), move |runner, reply| {
+        let current = current_value(&reply)?;
+        let target = current + delta;
+
+        return runner.kv_cas(KV_SERVICE, COUNTER_KEY.into(), current.into(), target.into(), true, move |runner, reply| {
+            match &reply.body.payload {
+                Payload::KvCasOk => return runner.reply(&msg, Payload::AddOk),
+                Payload::Error { code, .. } if *code == ErrorCode::PreconditionFailed => {
+                    return add(runner, delta, msg);
+                },
+                _ => bail!("unexpected reply to kv cas: {reply:?}"),
+            }
+        });
+    });
+}
+
+/// Extracts the counter's value from a `kv_read` reply, treating a missing
+/// key as zero.
+fn current_value(reply: &Message) -> anyhow::Result<i64> {
+    match &reply.body.payload {
+        Payload::KvReadOk { value } => Ok(value.as_i64().unwrap_or(0)),
+        Payload::Error { code, .. } if *code == ErrorCode::KeyDoesNotExist => Ok(0),
+        _ => bail!("unexpected reply to kv read: {reply:?}"),
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::bail;
+
+use crate::{
+    message::{Message, Payload},
+    node::Node,
+    runner::Runner,
+};
+
+/// Default `Node` impl covering the echo, unique-id-generation and
+/// gossip-based broadcast workloads.
+#[derive(Default)]
+pub struct EchoBroadcast {
+    messages: HashSet<usize>,
+    topology: HashMap<String, Vec<String>>,
+    // values we believe each neighbor already has, so we don't keep resending them
+    known: HashMap<String, HashSet<usize>>,
+}
+
+impl Node for EchoBroadcast {
+    fn handle(&mut self, runner: &Runner<Self>, msg: Message) -> anyhow::Result<()> {
+        match &msg.body.payload {
+            Payload::Init { node_id, .. } => {
+                runner.set_node_id(node_id.clone());
+                runner.reply(&msg, Payload::InitOk)?;
+            },
+            Payload::InitOk => bail!("node received init_ok message"),
+
+            Payload::Echo { echo } => {
+                let echo = echo.clone();
+                runner.reply(&msg, Payload::EchoOk { echo })?;
+            },
+            Payload::EchoOk { .. } => {},
+
+            Payload::Generate => {
+                // node_id's uniqueness is guarenteed by the network
+                // msg_id's uniqueness is guarenteed by the node implementation
+                let unique_id = format!("{}#{}", runner.node_id(), runner.msg_id());
+
+                runner.reply(&msg, Payload::GenerateOk { id: unique_id })?;
+            },
+            Payload::GenerateOk { .. } => bail!("node received generate_ok message"),
+
+            Payload::Broadcast { message } => {
+                self.messages.insert(*message);
+                runner.reply(&msg, Payload::BroadcastOk)?;
+            },
+            Payload::BroadcastOk => {},
+
+            Payload::Read => {
+                let messages = self.messages.iter().copied().collect();
+                runner.reply(&msg, Payload::ReadOk { messages })?;
+            },
+            Payload::ReadOk { .. } => {},
+
+            Payload::Topology { topology } => {
+                self.topology = topology.clone();
+                runner.reply(&msg, Payload::TopologyOk)?;
+            },
+            Payload::TopologyOk => {},
+
+            Payload::Gossip { messages } => {
+                let messages = messages.clone();
+                self.messages.extend(&messages);
+                runner.reply(&msg, Payload::GossipOk { messages })?;
+            },
+            Payload::GossipOk { messages } => {
+                let known = self.known.entry(msg.src.clone()).or_default();
+                known.extend(messages);
+            },
+
+            Payload::Tick => self.gossip(runner)?,
+
+            // KV and counter-workload payloads: not part of this node's protocol.
+            Payload::KvRead { .. }
+            | Payload::KvReadOk { .. }
+            | Payload::KvWrite { .. }
+            | Payload::KvWriteOk
+            | Payload::KvCas { .. }
+            | Payload::KvCasOk
+            | Payload::Error { .. }
+            | Payload::Add { .. }
+            | Payload::AddOk
+            | Payload::CounterRead
+            | Payload::CounterReadOk { .. } => {},
+        };
+
+        return Ok(());
+    }
+}
+
+impl EchoBroadcast {
+    /// Sends each neighbor the values we believe it doesn't have yet.
+    fn gossip(&mut self, runner: &Runner<Self>) -> anyhow::Result<()> {
+        let neighbors = self.topology.get(&runner.node_id()).cloned().unwrap_or_default();
+
+        for neighbor in neighbors {
+            let known = self.known.entry(neighbor.clone()).or_default();
+            let diff: Vec<usize> = self.messages.difference(known).copied().collect();
+
+            if diff.is_empty() {
+                continue;
+            }
+
+            runner.send(neighbor, Payload::Gossip { messages: diff })?;
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Message {
+    pub src: String,
+    pub dest: String,
+    pub body: Body,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Body {
+    pub msg_id: Option<usize>,
+    pub in_reply_to: Option<usize>,
+
+    #[serde(flatten)]
+    pub payload: Payload,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+#[serde(tag = "type")]
+pub enum Payload {
+    Init {
+        node_id: String,
+        node_ids: Vec<String>,
+    },
+    InitOk,
+
+    Echo {
+        echo: String,
+    },
+    EchoOk {
+        echo: String,
+    },
+
+    Generate,
+    GenerateOk {
+        id: String,
+    },
+
+    Broadcast {
+        message: usize,
+    },
+    BroadcastOk,
+
+    Read,
+    ReadOk {
+        messages: Vec<usize>,
+    },
+
+    Topology {
+        topology: HashMap<String, Vec<String>>,
+    },
+    TopologyOk,
+
+    Gossip {
+        messages: Vec<usize>,
+    },
+    GossipOk {
+        messages: Vec<usize>,
+    },
+
+    // Maelstrom's built-in seq-kv/lin-kv services, addressed like any other node.
+    KvRead {
+        key: Value,
+    },
+    KvReadOk {
+        value: Value,
+    },
+    KvWrite {
+        key: Value,
+        value: Value,
+    },
+    KvWriteOk,
+    KvCas {
+        key: Value,
+        from: Value,
+        to: Value,
+        #[serde(default)]
+        create_if_not_exists: bool,
+    },
+    KvCasOk,
+
+    Error {
+        code: ErrorCode,
+        text: String,
+    },
+
+    Add {
+        delta: i64,
+    },
+    AddOk,
+
+    CounterRead,
+    CounterReadOk {
+        value: i64,
+    },
+
+    /// Synthetic, self-addressed message injected through `Runner::backdoor`
+    /// by a timer thread; never sent over the network.
+    Tick,
+}
+
+/// Maelstrom's standard error codes, serialized to/from the plain integer
+/// the protocol puts on the wire. `Other` covers codes outside the standard
+/// set (e.g. app-defined codes >= 1000) so an unfamiliar code doesn't fail
+/// deserialization of the whole message.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorCode {
+    Timeout,
+    NotSupported,
+    TemporarilyUnavailable,
+    Crash,
+    KeyDoesNotExist,
+    PreconditionFailed,
+    Other(u8),
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let code = match self {
+            Self::Timeout => 0,
+            Self::NotSupported => 10,
+            Self::TemporarilyUnavailable => 11,
+            Self::Crash => 13,
+            Self::KeyDoesNotExist => 20,
+            Self::PreconditionFailed => 22,
+            Self::Other(code) => *code,
+        };
+
+        return serializer.serialize_u8(code);
+    }
+}
+
+impl<'de> Deserialize<'de> for ErrorCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let code = u8::deserialize(deserializer)?;
+
+        return Ok(match code {
+            0 => Self::Timeout,
+            10 => Self::NotSupported,
+            11 => Self::TemporarilyUnavailable,
+            13 => Self::Crash,
+            20 => Self::KeyDoesNotExist,
+            22 => Self::PreconditionFailed,
+            other => Self::Other(other),
+        });
+    }
+}
@@ -0,0 +1,12 @@
+use crate::{message::Message, runner::Runner};
+
+/// A workload's protocol handler, driven by a `Runner`.
+///
+/// Implement this to react to incoming `Message`s; the `Runner` passed in
+/// owns the I/O loop, `node_id` and outgoing `msg_id` counter, and exposes
+/// `reply`/`send` to talk back to the network.
+pub trait Node {
+    fn handle(&mut self, runner: &Runner<Self>, msg: Message) -> anyhow::Result<()>
+    where
+        Self: Sized;
+}
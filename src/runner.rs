@@ -0,0 +1,278 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, StdoutLock, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{
+    message::{Body, ErrorCode, Message, Payload},
+    node::Node,
+};
+
+/// A callback registered by `rpc`, invoked with the reply that correlates to
+/// the request via `in_reply_to` instead of being routed to `Node::handle`.
+type RpcCallback<N> = Box<dyn FnOnce(&Runner<N>, Message) -> anyhow::Result<()>>;
+
+struct PendingRpc<N> {
+    callback: RpcCallback<N>,
+    sent_at: Instant,
+    timeout: Option<Duration>,
+}
+
+/// A callback registered by `on_init`, run once after the `Init` message has
+/// been handled, so it can spawn timer threads knowing the node id.
+type OnInit<N> = Box<dyn FnOnce(&Runner<N>)>;
+
+/// Owns the stdin/stdout I/O loop for a Maelstrom node: the `node_id`, the
+/// monotonic outgoing `msg_id` counter, dispatch into a `Node` impl, and
+/// pending RPC callbacks keyed on the `msg_id` they're waiting on a reply to.
+///
+/// Input doesn't come directly from stdin: a reader thread parses lines and
+/// forwards them over a channel, whose sending half is also reachable
+/// through `backdoor` so timers and other background work can inject
+/// synthetic messages through the same dispatch path as real network input.
+pub struct Runner<N> {
+    node: RefCell<N>,
+    node_id: RefCell<Option<String>>,
+    msg_id: Cell<usize>,
+    output: RefCell<StdoutLock<'static>>,
+    pending: RefCell<HashMap<usize, PendingRpc<N>>>,
+    sender: Sender<Message>,
+    receiver: RefCell<Receiver<Message>>,
+    on_init: RefCell<Option<OnInit<N>>>,
+}
+
+impl<N: Node> Runner<N> {
+    pub fn new(node: N) -> Self {
+        // leaked so the lock can outlive `new`'s stack frame; this runs for
+        // the lifetime of the process anyway.
+        let stdout: &'static std::io::Stdout = Box::leak(Box::new(std::io::stdout()));
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            node: RefCell::new(node),
+            node_id: RefCell::new(None),
+            msg_id: Cell::new(0),
+            output: RefCell::new(stdout.lock()),
+            pending: RefCell::new(HashMap::new()),
+            sender,
+            receiver: RefCell::new(receiver),
+            on_init: RefCell::new(None),
+        }
+    }
+
+    /// Registers a callback to run once, right after the `Init` message has
+    /// been handled. Use it to spawn timer threads that inject ticks through
+    /// `backdoor` now that the node id is known.
+    pub fn on_init(self, callback: impl FnOnce(&Runner<N>) + 'static) -> Self {
+        *self.on_init.borrow_mut() = Some(Box::new(callback));
+        return self;
+    }
+
+    /// A clone of the sending half of the input channel, so application code
+    /// can inject synthetic `Message`s that are dispatched exactly like
+    /// messages read from stdin.
+    pub fn backdoor(&self) -> Sender<Message> {
+        return self.sender.clone();
+    }
+
+    pub fn node_id(&self) -> String {
+        self.node_id.borrow().clone().expect("node to be initialized")
+    }
+
+    pub(crate) fn set_node_id(&self, node_id: String) {
+        *self.node_id.borrow_mut() = Some(node_id);
+    }
+
+    /// The outgoing `msg_id` that will be used by the next `reply`/`send`.
+    pub fn msg_id(&self) -> usize {
+        self.msg_id.get()
+    }
+
+    fn next_msg_id(&self) -> usize {
+        let id = self.msg_id.get();
+        self.msg_id.set(id + 1);
+        return id;
+    }
+
+    pub fn reply(&self, msg: &Message, payload: Payload) -> anyhow::Result<()> {
+        let reply = Message {
+            src: self.node_id(),
+            dest: msg.src.clone(),
+            body: Body {
+                msg_id: Some(self.next_msg_id()),
+                in_reply_to: msg.body.msg_id,
+                payload,
+            },
+        };
+
+        return self.write(&reply);
+    }
+
+    pub fn send(&self, dest: impl Into<String>, payload: Payload) -> anyhow::Result<usize> {
+        let msg_id = self.next_msg_id();
+        let message = Message {
+            src: self.node_id(),
+            dest: dest.into(),
+            body: Body {
+                msg_id: Some(msg_id),
+                in_reply_to: None,
+                payload,
+            },
+        };
+
+        self.write(&message)?;
+
+        return Ok(msg_id);
+    }
+
+    /// Sends `payload` to `dest` and registers `callback` to run on the
+    /// reply that carries the outgoing `msg_id` in its `in_reply_to`,
+    /// instead of routing that reply to `Node::handle`.
+    ///
+    /// `timeout`, if set, bounds how long the callback may wait; expired
+    /// callbacks are dropped by `reap_expired_rpcs`.
+    pub fn rpc(
+        &self,
+        dest: impl Into<String>,
+        payload: Payload,
+        timeout: Option<Duration>,
+        callback: impl FnOnce(&Runner<N>, Message) -> anyhow::Result<()> + 'static,
+    ) -> anyhow::Result<()> {
+        let msg_id = self.send(dest, payload)?;
+
+        self.pending.borrow_mut().insert(msg_id, PendingRpc {
+            callback: Box::new(callback),
+            sent_at: Instant::now(),
+            timeout,
+        });
+
+        return Ok(());
+    }
+
+    /// Reads `key` from a Maelstrom KV service node (e.g. `"seq-kv"`).
+    pub fn kv_read(
+        &self,
+        service: impl Into<String>,
+        key: serde_json::Value,
+        callback: impl FnOnce(&Runner<N>, Message) -> anyhow::Result<()> + 'static,
+    ) -> anyhow::Result<()> {
+        return self.rpc(service, Payload::KvRead { key }, None, callback);
+    }
+
+    /// Compare-and-swaps `key` from `from` to `to` on a Maelstrom KV service node.
+    pub fn kv_cas(
+        &self,
+        service: impl Into<String>,
+        key: serde_json::Value,
+        from: serde_json::Value,
+        to: serde_json::Value,
+        create_if_not_exists: bool,
+        callback: impl FnOnce(&Runner<N>, Message) -> anyhow::Result<()> + 'static,
+    ) -> anyhow::Result<()> {
+        return self.rpc(service, Payload::KvCas { key, from, to, create_if_not_exists }, None, callback);
+    }
+
+    /// Drops pending RPC callbacks whose timeout has elapsed without a reply.
+    pub fn reap_expired_rpcs(&self) {
+        let now = Instant::now();
+        self.pending.borrow_mut().retain(|_, pending| {
+            match pending.timeout {
+                Some(timeout) => now.duration_since(pending.sent_at) < timeout,
+                None => true,
+            }
+        });
+    }
+
+    fn write(&self, message: &Message) -> anyhow::Result<()> {
+        let mut output = self.output.borrow_mut();
+        serde_json::to_writer(&mut *output, message)?;
+        output.write_all(b"\n")?;
+
+        return Ok(());
+    }
+
+    pub fn run(&self) -> anyhow::Result<()> {
+        let sender = self.sender.clone();
+        thread::spawn(move || read_stdin(sender));
+
+        while let Ok(input) = self.receiver.borrow().recv() {
+            self.reap_expired_rpcs();
+
+            let is_init = matches!(input.body.payload, Payload::Init { .. });
+            let src = input.src.clone();
+            let in_reply_to = input.body.msg_id;
+
+            let pending = input.body.in_reply_to.and_then(|id| self.pending.borrow_mut().remove(&id));
+            let is_pending_reply = pending.is_some();
+            let result = match pending {
+                Some(pending) => (pending.callback)(self, input),
+                None => self.node.borrow_mut().handle(self, input),
+            };
+
+            match result {
+                Ok(()) => {
+                    if is_init {
+                        if let Some(on_init) = self.on_init.borrow_mut().take() {
+                            on_init(self);
+                        }
+                    }
+                },
+                // A handler error shouldn't take the whole node down: tell the
+                // sender and keep serving requests, so Maelstrom's fault
+                // injection can be debugged instead of just killing the
+                // process. An RPC callback's error has no sender to report
+                // to (`src`/`in_reply_to` here belong to the RPC reply, not
+                // the original request), so it's just logged.
+                Err(err) if is_pending_reply => eprintln!("rpc callback failed: {err:#}"),
+                Err(err) => self.send_error(src, in_reply_to, ErrorCode::Crash, format!("{err:#}"))?,
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn send_error(&self, dest: impl Into<String>, in_reply_to: Option<usize>, code: ErrorCode, text: String) -> anyhow::Result<()> {
+        let message = Message {
+            src: self.node_id(),
+            dest: dest.into(),
+            body: Body {
+                msg_id: Some(self.next_msg_id()),
+                in_reply_to,
+                payload: Payload::Error { code, text },
+            },
+        };
+
+        return self.write(&message);
+    }
+}
+
+/// Reads newline-delimited `Message`s from stdin and forwards them over
+/// `sender`, so the main loop never blocks on I/O directly. Exits once
+/// stdin closes or the receiving half is dropped.
+fn read_stdin(sender: Sender<Message>) {
+    let stdin = std::io::stdin();
+
+    for line in BufReader::new(stdin.lock()).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("failed to read from stdin: {err}");
+                break;
+            },
+        };
+
+        let message = match serde_json::from_str::<Message>(&line) {
+            Ok(message) => message,
+            Err(err) => {
+                eprintln!("failed to deserialize input {line:?}: {err}");
+                continue;
+            },
+        };
+
+        if sender.send(message).is_err() {
+            break;
+        }
+    }
+}